@@ -0,0 +1,492 @@
+//! ARC (RFC 8617) signing, built on the same canonicalization/hash
+//! machinery as [`crate::sign::Signer`].
+
+use ed25519_dalek::ExpandedSecretKey;
+use rsa::PaddingScheme;
+use sha1::{Digest as _, Sha1};
+use sha2::{Digest as _, Sha256};
+
+use crate::header::DKIMHeaderBuilder;
+use crate::{canonicalization, hash, DKIMError, DkimPrivateKey};
+
+const ARC_AUTH_RESULTS: &str = "ARC-Authentication-Results";
+const ARC_MESSAGE_SIGNATURE: &str = "ARC-Message-Signature";
+const ARC_SEAL: &str = "ARC-Seal";
+
+/// Parse the exact `i=` tag value off one of this message's existing ARC
+/// headers. A plain substring check would wrongly match instance 1 against
+/// an "i=10" header, so each tag is matched up to its delimiter.
+fn header_instance(header: &mailparse::MailHeader) -> Option<u32> {
+    header.get_value().split(';').find_map(|part| {
+        let (key, value) = part.trim().split_once('=')?;
+        (key.trim() == "i").then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+/// Split a full `Name: value` header line and canonicalize it the same way
+/// as a header field read off a parsed message.
+fn canonicalize_header_line(canonicalization: canonicalization::Type, line: &str) -> Vec<u8> {
+    let (name, value) = line.split_once(':').unwrap_or((line, ""));
+    canonicalization::canonicalize_header(canonicalization, name, value.trim_start())
+}
+
+/// Outcome of validating the ARC chain up to (but not including) the
+/// instance currently being sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainValidation {
+    Pass,
+    Fail,
+}
+
+/// The three header lines making up one ARC set, ready to be prepended to
+/// the message in this order.
+#[derive(Debug, Clone)]
+pub struct ArcSet {
+    pub arc_authentication_results: String,
+    pub arc_message_signature: String,
+    pub arc_seal: String,
+}
+
+/// Builder for [`ArcSigner`].
+#[derive(Default)]
+pub struct ArcSignerBuilder<'a> {
+    signed_headers: Option<&'a [&'a str]>,
+    private_key: Option<DkimPrivateKey>,
+    selector: Option<&'a str>,
+    signing_domain: Option<&'a str>,
+    time: Option<time::OffsetDateTime>,
+}
+
+impl<'a> ArcSignerBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify headers to cover with the `ARC-Message-Signature`. The From:
+    /// header is required, as for [`crate::SignerBuilder`].
+    pub fn with_signed_headers(mut self, headers: &'a [&'a str]) -> Result<Self, DKIMError> {
+        let from = headers.iter().find(|h| h.to_lowercase() == "from");
+        if from.is_none() {
+            return Err(DKIMError::BuilderError("missing From in signed headers"));
+        }
+        self.signed_headers = Some(headers);
+        Ok(self)
+    }
+
+    /// Specify the private key used to seal the ARC set.
+    pub fn with_private_key(mut self, key: DkimPrivateKey) -> Self {
+        self.private_key = Some(key);
+        self
+    }
+
+    /// Specify the selector used to seal the ARC set.
+    pub fn with_selector(mut self, value: &'a str) -> Self {
+        self.selector = Some(value);
+        self
+    }
+
+    /// Specify the domain the ARC set is sealed for.
+    pub fn with_signing_domain(mut self, value: &'a str) -> Self {
+        self.signing_domain = Some(value);
+        self
+    }
+
+    /// Specify current time. Mostly used for testing.
+    pub fn with_time(mut self, value: time::OffsetDateTime) -> Self {
+        self.time = Some(value);
+        self
+    }
+
+    pub fn build(self) -> Result<ArcSigner<'a>, DKIMError> {
+        use DKIMError::BuilderError;
+
+        let private_key = self
+            .private_key
+            .ok_or(BuilderError("missing required private key"))?;
+        let hash_algo = match private_key {
+            DkimPrivateKey::Rsa(_) => hash::HashAlgo::RsaSha256,
+            DkimPrivateKey::Ed25519(_) => hash::HashAlgo::Ed25519Sha256,
+        };
+
+        Ok(ArcSigner {
+            signed_headers: self
+                .signed_headers
+                .ok_or(BuilderError("missing required signed headers"))?,
+            private_key,
+            selector: self
+                .selector
+                .ok_or(BuilderError("missing required selector"))?,
+            signing_domain: self
+                .signing_domain
+                .ok_or(BuilderError("missing required signing domain"))?,
+            hash_algo,
+            time: self.time,
+        })
+    }
+}
+
+/// ARC signer. Use [`ArcSignerBuilder`] to build an instance.
+pub struct ArcSigner<'a> {
+    signed_headers: &'a [&'a str],
+    private_key: DkimPrivateKey,
+    selector: &'a str,
+    signing_domain: &'a str,
+    hash_algo: hash::HashAlgo,
+    time: Option<time::OffsetDateTime>,
+}
+
+impl<'a> ArcSigner<'a> {
+    /// Seal `email` as ARC instance `instance` (one higher than the highest
+    /// existing ARC instance number found on the message, or `1` if none),
+    /// carrying `authentication_results` verbatim in the new
+    /// `ARC-Authentication-Results` header, and recording the chain
+    /// validation result for all prior instances (`None` for `instance ==
+    /// 1`, i.e. `cv=none`).
+    ///
+    /// As specified in <https://datatracker.ietf.org/doc/html/rfc8617>.
+    pub fn seal<'b>(
+        &self,
+        email: &'b mailparse::ParsedMail<'b>,
+        instance: u32,
+        authentication_results: &str,
+        chain_validation: Option<ChainValidation>,
+    ) -> Result<ArcSet, DKIMError> {
+        if instance == 1 && chain_validation.is_some() {
+            return Err(DKIMError::BuilderError(
+                "chain_validation must be None for the first ARC instance (cv=none)",
+            ));
+        }
+        if instance != 1 && chain_validation.is_none() {
+            return Err(DKIMError::BuilderError(
+                "chain_validation is required for any ARC instance after the first",
+            ));
+        }
+
+        let body_hash = hash::compute_body_hash(
+            canonicalization::Type::Relaxed,
+            None,
+            self.hash_algo,
+            email,
+        )?;
+
+        let aar_line = format!(
+            "{}: i={}; {}",
+            ARC_AUTH_RESULTS, instance, authentication_results
+        );
+
+        let ams_builder = self.ams_header_builder(&body_hash, instance)?;
+        let ams_header_for_hash = ams_builder.clone().add_tag("b", "").build()?;
+        let signed_headers = ams_header_for_hash.get_required_tag("h");
+        let ams_header_hash = hash::compute_headers_hash(
+            canonicalization::Type::Relaxed,
+            &signed_headers,
+            self.hash_algo,
+            &ams_header_for_hash,
+            email,
+        )?;
+        let ams_signature = self.sign(&ams_header_hash)?;
+        let ams_header = ams_builder
+            .add_tag("b", &base64::encode(&ams_signature))
+            .build()?;
+        let ams_line = format!("{}: {}", ARC_MESSAGE_SIGNATURE, ams_header.raw_bytes);
+
+        let cv = match chain_validation {
+            None => "none",
+            Some(ChainValidation::Pass) => "pass",
+            Some(ChainValidation::Fail) => "fail",
+        };
+        let seal_builder = self.seal_header_builder(instance, cv)?;
+        let seal_hash =
+            self.compute_seal_hash(email, seal_builder.clone(), instance, &aar_line, &ams_line)?;
+        let seal_signature = self.sign(&seal_hash)?;
+        let seal_header = seal_builder
+            .add_tag("b", &base64::encode(&seal_signature))
+            .build()?;
+        let seal_line = format!("{}: {}", ARC_SEAL, seal_header.raw_bytes);
+
+        Ok(ArcSet {
+            arc_authentication_results: aar_line,
+            arc_message_signature: ams_line,
+            arc_seal: seal_line,
+        })
+    }
+
+    fn hash_algo_name(&self) -> &'static str {
+        match self.hash_algo {
+            hash::HashAlgo::RsaSha1 => "rsa-sha1",
+            hash::HashAlgo::RsaSha256 => "rsa-sha256",
+            hash::HashAlgo::Ed25519Sha256 => "ed25519-sha256",
+        }
+    }
+
+    fn ams_header_builder(
+        &self,
+        body_hash: &str,
+        instance: u32,
+    ) -> Result<DKIMHeaderBuilder, DKIMError> {
+        let now = self.time.unwrap_or_else(time::OffsetDateTime::now_utc);
+        let builder = DKIMHeaderBuilder::new()
+            .add_tag("i", &instance.to_string())
+            .add_tag("a", self.hash_algo_name())
+            .add_tag("d", self.signing_domain)
+            .add_tag("s", self.selector)
+            .add_tag("c", "relaxed/relaxed")
+            .add_tag("bh", body_hash)
+            .set_signed_headers(
+                &self
+                    .signed_headers
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .set_time(now);
+        Ok(builder)
+    }
+
+    fn seal_header_builder(&self, instance: u32, cv: &str) -> Result<DKIMHeaderBuilder, DKIMError> {
+        let now = self.time.unwrap_or_else(time::OffsetDateTime::now_utc);
+        let builder = DKIMHeaderBuilder::new()
+            .add_tag("i", &instance.to_string())
+            .add_tag("a", self.hash_algo_name())
+            .add_tag("d", self.signing_domain)
+            .add_tag("s", self.selector)
+            .add_tag("t", &now.unix_timestamp().to_string())
+            .add_tag("cv", cv);
+        Ok(builder)
+    }
+
+    /// The `ARC-Seal` signature covers every ARC header field of every
+    /// instance up to and including this one, in instance order, relaxed
+    /// canonicalization, ending with the seal itself (with `b=` emptied).
+    fn compute_seal_hash<'b>(
+        &self,
+        email: &'b mailparse::ParsedMail<'b>,
+        seal_builder: DKIMHeaderBuilder,
+        instance: u32,
+        current_aar: &str,
+        current_ams: &str,
+    ) -> Result<Vec<u8>, DKIMError> {
+        let mut canonical = Vec::new();
+
+        for i in 1..instance {
+            for name in [ARC_AUTH_RESULTS, ARC_MESSAGE_SIGNATURE, ARC_SEAL] {
+                if let Some(header) = email.headers.iter().find(|h| {
+                    h.get_key_ref().eq_ignore_ascii_case(name) && header_instance(h) == Some(i)
+                }) {
+                    canonical.extend_from_slice(&canonicalization::canonicalize_header(
+                        canonicalization::Type::Relaxed,
+                        header.get_key_ref(),
+                        &header.get_value(),
+                    ));
+                }
+            }
+        }
+
+        for line in [current_aar, current_ams] {
+            canonical.extend_from_slice(&canonicalize_header_line(
+                canonicalization::Type::Relaxed,
+                line,
+            ));
+        }
+
+        // The ARC-Seal being created is itself the last signed header field,
+        // so (as with DKIM-Signature in RFC 6376 §3.7) it is canonicalized
+        // with its trailing CRLF stripped.
+        let seal_header = seal_builder.add_tag("b", "").build()?;
+        let mut seal_bytes = canonicalization::canonicalize_header(
+            canonicalization::Type::Relaxed,
+            ARC_SEAL,
+            &seal_header.raw_bytes,
+        );
+        seal_bytes.truncate(seal_bytes.len() - 2);
+        canonical.extend_from_slice(&seal_bytes);
+
+        Ok(match self.hash_algo {
+            hash::HashAlgo::RsaSha1 => Sha1::digest(&canonical).to_vec(),
+            hash::HashAlgo::RsaSha256 | hash::HashAlgo::Ed25519Sha256 => {
+                Sha256::digest(&canonical).to_vec()
+            }
+        })
+    }
+
+    fn sign(&self, digest: &[u8]) -> Result<Vec<u8>, DKIMError> {
+        match &self.private_key {
+            DkimPrivateKey::Rsa(private_key) => private_key
+                .sign(
+                    match &self.hash_algo {
+                        hash::HashAlgo::RsaSha1 => PaddingScheme::new_pkcs1v15_sign::<Sha1>(),
+                        hash::HashAlgo::RsaSha256 => PaddingScheme::new_pkcs1v15_sign::<Sha256>(),
+                        hash => {
+                            return Err(DKIMError::UnsupportedHashAlgorithm(format!("{:?}", hash)))
+                        }
+                    },
+                    digest,
+                )
+                .map_err(|err| DKIMError::FailedToSign(err.to_string())),
+            DkimPrivateKey::Ed25519(keypair) => {
+                let expanded: ExpandedSecretKey = (&keypair.secret).into();
+                Ok(expanded.sign(digest, &keypair.public).to_bytes().into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs1::DecodeRsaPrivateKey;
+    use rsa::PublicKey as _;
+    use std::path::Path;
+    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+    #[test]
+    fn test_seal_first_instance() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let time = OffsetDateTime::parse("2021-01-01T00:00:01.444Z", &Rfc3339).unwrap();
+
+        let signer = ArcSignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_time(time)
+            .build()
+            .unwrap();
+
+        let arc_set = signer.seal(&email, 1, "dkim=pass", None).unwrap();
+
+        assert!(arc_set.arc_seal.contains("i=1"));
+        assert!(arc_set.arc_seal.contains("cv=none"));
+        assert_eq!(
+            arc_set.arc_authentication_results,
+            "ARC-Authentication-Results: i=1; dkim=pass"
+        );
+        assert!(arc_set.arc_message_signature.contains("i=1"));
+    }
+
+    #[test]
+    fn test_seal_rejects_cv_for_first_instance() {
+        let email = mailparse::parse_mail(b"From: a@example.com\r\n\r\nhi\r\n").unwrap();
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let signer = ArcSignerBuilder::new()
+            .with_signed_headers(&["From"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+
+        let err = signer
+            .seal(&email, 1, "dkim=pass", Some(ChainValidation::Pass))
+            .unwrap_err();
+        assert!(matches!(err, DKIMError::BuilderError(_)));
+    }
+
+    #[test]
+    fn test_seal_requires_cv_after_first_instance() {
+        let email = mailparse::parse_mail(b"From: a@example.com\r\n\r\nhi\r\n").unwrap();
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let signer = ArcSignerBuilder::new()
+            .with_signed_headers(&["From"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+
+        let err = signer.seal(&email, 2, "dkim=pass", None).unwrap_err();
+        assert!(matches!(err, DKIMError::BuilderError(_)));
+    }
+
+    #[test]
+    fn test_seal_second_instance_chains_prior_arc_headers() {
+        let instance1_email_text =
+            "Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\nHello Alice\r\n";
+        let email = mailparse::parse_mail(instance1_email_text.as_bytes()).unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let time = OffsetDateTime::parse("2021-01-01T00:00:01.444Z", &Rfc3339).unwrap();
+
+        let signer = ArcSignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key.clone()))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_time(time)
+            .build()
+            .unwrap();
+
+        let instance1 = signer.seal(&email, 1, "dkim=pass", None).unwrap();
+        let raw_with_instance1 = format!(
+            "{}\r\n{}\r\n{}\r\n{}",
+            instance1.arc_seal,
+            instance1.arc_message_signature,
+            instance1.arc_authentication_results,
+            instance1_email_text
+        );
+        let email_with_instance1 = mailparse::parse_mail(raw_with_instance1.as_bytes()).unwrap();
+
+        let instance2 = signer
+            .seal(&email_with_instance1, 2, "dkim=pass", Some(ChainValidation::Pass))
+            .unwrap();
+        assert!(instance2.arc_seal.contains("i=2"));
+        assert!(instance2.arc_seal.contains("cv=pass"));
+
+        // Recompute the canonical bytes the same way `seal` did (covering
+        // instance 1's ARC headers, the new AAR/AMS lines, and the new seal
+        // with b= emptied) and check the produced signature verifies
+        // against it with the public key.
+        let seal_builder = signer.seal_header_builder(2, "pass").unwrap();
+        let expected_hash = signer
+            .compute_seal_hash(
+                &email_with_instance1,
+                seal_builder,
+                2,
+                &instance2.arc_authentication_results,
+                &instance2.arc_message_signature,
+            )
+            .unwrap();
+        let seal_tags = crate::header::DKIMHeader::parse(
+            instance2
+                .arc_seal
+                .trim_start_matches(&format!("{}: ", ARC_SEAL)),
+        )
+        .unwrap();
+        let signature = base64::decode(seal_tags.get_tag("b").unwrap()).unwrap();
+        let public_key = rsa::RsaPublicKey::from(&private_key);
+        public_key
+            .verify(
+                PaddingScheme::new_pkcs1v15_sign::<Sha256>(),
+                &expected_hash,
+                &signature,
+            )
+            .unwrap();
+
+        // If the chain-covering loop didn't actually read instance 1's ARC
+        // headers, tampering with them wouldn't change what gets signed.
+        let tampered_raw = raw_with_instance1.replacen("dkim=pass", "dkim=fail", 1);
+        let tampered_email = mailparse::parse_mail(tampered_raw.as_bytes()).unwrap();
+        let instance2_tampered = signer
+            .seal(&tampered_email, 2, "dkim=pass", Some(ChainValidation::Pass))
+            .unwrap();
+        assert_ne!(instance2.arc_seal, instance2_tampered.arc_seal);
+    }
+}
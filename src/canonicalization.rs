@@ -0,0 +1,175 @@
+//! Header and body canonicalization, see
+//! <https://datatracker.ietf.org/doc/html/rfc6376#section-3.4>.
+
+use std::fmt;
+
+/// Canonicalization algorithm applied to headers or body before hashing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Simple,
+    Relaxed,
+}
+
+impl Default for Type {
+    fn default() -> Self {
+        Type::Simple
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Simple => write!(f, "simple"),
+            Type::Relaxed => write!(f, "relaxed"),
+        }
+    }
+}
+
+impl std::str::FromStr for Type {
+    type Err = crate::DKIMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" => Ok(Type::Simple),
+            "relaxed" => Ok(Type::Relaxed),
+            other => Err(crate::DKIMError::UnsupportedCanonicalizationType(
+                other.to_string(),
+            )),
+        }
+    }
+}
+
+/// Parse a `c=header/body` tag value, defaulting the body side to simple
+/// when it is omitted as permitted by the RFC.
+pub(crate) fn parse_pair(value: &str) -> Result<(Type, Type), crate::DKIMError> {
+    let mut parts = value.splitn(2, '/');
+    let header = parts
+        .next()
+        .ok_or_else(|| crate::DKIMError::UnsupportedCanonicalizationType(value.to_string()))?
+        .parse()?;
+    let body = match parts.next() {
+        Some(body) => body.parse()?,
+        None => Type::Simple,
+    };
+    Ok((header, body))
+}
+
+pub(crate) fn canonicalize_body(canonicalization: Type, body: &[u8]) -> Vec<u8> {
+    match canonicalization {
+        Type::Simple => canonicalize_body_simple(body),
+        Type::Relaxed => canonicalize_body_relaxed(body),
+    }
+}
+
+/// Canonicalize one header field, producing the bytes to hash including the
+/// trailing CRLF. Per RFC 6376 §3.4.1/§3.4.2: simple mode leaves the name
+/// and value untouched; relaxed mode lowercases the name, drops the space
+/// after the colon, trims leading and trailing WSP from the value, and
+/// collapses internal WSP runs to a single SP.
+pub(crate) fn canonicalize_header(canonicalization: Type, name: &str, value: &str) -> Vec<u8> {
+    match canonicalization {
+        Type::Simple => format!("{}: {}\r\n", name, value).into_bytes(),
+        Type::Relaxed => {
+            let name = name.to_lowercase();
+            let value = collapse_wsp(value.trim());
+            format!("{}:{}\r\n", name, value).into_bytes()
+        }
+    }
+}
+
+fn collapse_wsp(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut last_was_space = false;
+    for c in value.chars() {
+        if c == ' ' || c == '\t' || c == '\r' || c == '\n' {
+            last_was_space = true;
+        } else {
+            if last_was_space {
+                out.push(' ');
+            }
+            last_was_space = false;
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn canonicalize_body_simple(body: &[u8]) -> Vec<u8> {
+    if body.is_empty() {
+        return b"\r\n".to_vec();
+    }
+    let mut trimmed = body;
+    while trimmed.ends_with(b"\r\n\r\n") {
+        trimmed = &trimmed[..trimmed.len() - 2];
+    }
+    if trimmed.is_empty() {
+        return b"\r\n".to_vec();
+    }
+    if !trimmed.ends_with(b"\r\n") {
+        let mut owned = trimmed.to_vec();
+        owned.extend_from_slice(b"\r\n");
+        return owned;
+    }
+    trimmed.to_vec()
+}
+
+fn canonicalize_body_relaxed(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    for line in body.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let mut collapsed: Vec<u8> = Vec::with_capacity(line.len());
+        let mut last_was_space = false;
+        for &b in line {
+            if b == b' ' || b == b'\t' {
+                last_was_space = true;
+            } else {
+                if last_was_space {
+                    collapsed.push(b' ');
+                }
+                last_was_space = false;
+                collapsed.push(b);
+            }
+        }
+        out.extend_from_slice(&collapsed);
+        out.extend_from_slice(b"\r\n");
+    }
+    while out.ends_with(b"\r\n\r\n") {
+        out.truncate(out.len() - 2);
+    }
+    if out.is_empty() {
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_body_relaxed_collapses_internal_wsp() {
+        let body = b"a  b\ttc\r\n";
+        assert_eq!(canonicalize_body(Type::Relaxed, body), b"a b tc\r\n");
+    }
+
+    #[test]
+    fn test_canonicalize_body_relaxed_collapses_leading_wsp() {
+        let body = b"   indented line\r\nplain\r\n";
+        assert_eq!(
+            canonicalize_body(Type::Relaxed, body),
+            b" indented line\r\nplain\r\n"
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_header_relaxed_lowercases_name_and_collapses_value() {
+        let bytes = canonicalize_header(Type::Relaxed, "Subject", "  Hello   World  ");
+        assert_eq!(bytes, b"subject:Hello World\r\n");
+    }
+
+    #[test]
+    fn test_canonicalize_header_simple_preserves_case_and_spacing() {
+        let bytes = canonicalize_header(Type::Simple, "Subject", "Hello   World");
+        assert_eq!(bytes, b"Subject: Hello   World\r\n");
+    }
+}
@@ -0,0 +1,98 @@
+//! Computation of the body hash (`bh=`) and header hash used both when
+//! signing and when verifying a `DKIM-Signature`.
+
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use crate::header::DKIMHeader;
+use crate::{canonicalization, DKIMError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    RsaSha1,
+    RsaSha256,
+    Ed25519Sha256,
+}
+
+impl HashAlgo {
+    /// The hash function name as it appears in a DNS record's `h=` tag
+    /// (RFC 6376 §3.6.1), as opposed to the combined `a=` signature
+    /// algorithm name such as `rsa-sha256`.
+    pub(crate) fn dns_hash_name(&self) -> &'static str {
+        match self {
+            HashAlgo::RsaSha1 => "sha1",
+            HashAlgo::RsaSha256 | HashAlgo::Ed25519Sha256 => "sha256",
+        }
+    }
+}
+
+pub(crate) fn compute_body_hash<'a>(
+    canonicalization: canonicalization::Type,
+    length: Option<usize>,
+    hash_algo: HashAlgo,
+    email: &'a mailparse::ParsedMail<'a>,
+) -> Result<String, DKIMError> {
+    let canonical_body = canonicalization::canonicalize_body(canonicalization, email.get_body_raw().unwrap_or_default().as_slice());
+    let canonical_body = match length {
+        Some(length) => &canonical_body[..length.min(canonical_body.len())],
+        None => &canonical_body[..],
+    };
+
+    let digest = match hash_algo {
+        HashAlgo::RsaSha1 => Sha1::digest(canonical_body).to_vec(),
+        HashAlgo::RsaSha256 | HashAlgo::Ed25519Sha256 => Sha256::digest(canonical_body).to_vec(),
+    };
+    Ok(base64::encode(digest))
+}
+
+pub(crate) fn compute_headers_hash<'a>(
+    canonicalization: canonicalization::Type,
+    signed_headers: &str,
+    hash_algo: HashAlgo,
+    dkim_header: &DKIMHeader,
+    email: &'a mailparse::ParsedMail<'a>,
+) -> Result<Vec<u8>, DKIMError> {
+    let mut canonical = Vec::new();
+    // Tracks how many occurrences of a given header name have already been
+    // consumed, so that oversigned (repeated) entries in `h=` walk further
+    // up the message instead of re-signing the same header field: RFC 6376
+    // processes header fields from the bottom of the message upwards, and a
+    // name listed more times than it appears contributes nothing further.
+    let mut consumed: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for name in signed_headers.split(':') {
+        let skip = *consumed.get(name).unwrap_or(&0);
+        *consumed.entry(name.to_string()).or_insert(0) += 1;
+
+        if let Some(header) = email
+            .headers
+            .iter()
+            .rev()
+            .filter(|h| h.get_key_ref().eq_ignore_ascii_case(name))
+            .nth(skip)
+        {
+            canonical.extend_from_slice(&canonicalization::canonicalize_header(
+                canonicalization,
+                header.get_key_ref(),
+                &header.get_value(),
+            ));
+        }
+    }
+
+    // The DKIM-Signature header itself is canonicalized the same way as any
+    // other signed header, then has its trailing CRLF stripped since it is
+    // always the last hashed header field (RFC 6376 §3.7).
+    let mut dkim_header_line = canonicalization::canonicalize_header(
+        canonicalization,
+        crate::HEADER,
+        &dkim_header.raw_bytes,
+    );
+    dkim_header_line.truncate(dkim_header_line.len() - 2);
+    canonical.extend_from_slice(&dkim_header_line);
+
+    let digest = match hash_algo {
+        HashAlgo::RsaSha1 => Sha1::digest(&canonical).to_vec(),
+        HashAlgo::RsaSha256 | HashAlgo::Ed25519Sha256 => Sha256::digest(&canonical).to_vec(),
+    };
+    Ok(digest)
+}
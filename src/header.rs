@@ -0,0 +1,103 @@
+//! Construction and parsing of the raw `DKIM-Signature` tag-list.
+
+use crate::DKIMError;
+
+/// A parsed or in-progress `DKIM-Signature` header value.
+#[derive(Debug, Clone)]
+pub(crate) struct DKIMHeader {
+    pub(crate) tags: Vec<(String, String)>,
+    pub(crate) raw_bytes: String,
+}
+
+impl DKIMHeader {
+    pub(crate) fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn get_required_tag(&self, name: &str) -> String {
+        self.get_tag(name).unwrap_or_default().to_string()
+    }
+
+    /// Parse a raw `DKIM-Signature` tag-list, e.g. the value following the
+    /// header name and colon.
+    pub(crate) fn parse(raw: &str) -> Result<Self, DKIMError> {
+        let mut tags = Vec::new();
+        for part in raw.split(';') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| DKIMError::SignatureSyntaxError(part.to_string()))?;
+            tags.push((key.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(DKIMHeader {
+            tags,
+            raw_bytes: raw.to_string(),
+        })
+    }
+}
+
+/// Builder used by [`crate::Signer`] to assemble the `DKIM-Signature` value
+/// tag by tag, in the order they should appear on the wire.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DKIMHeaderBuilder {
+    tags: Vec<(String, String)>,
+}
+
+impl DKIMHeaderBuilder {
+    pub(crate) fn new() -> Self {
+        Self { tags: Vec::new() }
+    }
+
+    /// Add or replace a tag, preserving first-insertion order.
+    pub(crate) fn add_tag(mut self, name: &str, value: &str) -> Self {
+        if let Some(existing) = self.tags.iter_mut().find(|(key, _)| key == name) {
+            existing.1 = value.to_string();
+        } else {
+            self.tags.push((name.to_string(), value.to_string()));
+        }
+        self
+    }
+
+    pub(crate) fn set_signed_headers(self, headers: &[String]) -> Self {
+        let joined = headers
+            .iter()
+            .map(|h| h.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(":");
+        self.add_tag("h", &joined)
+    }
+
+    pub(crate) fn set_time(self, time: time::OffsetDateTime) -> Self {
+        self.add_tag("t", &time.unix_timestamp().to_string())
+    }
+
+    pub(crate) fn set_expiry(self, expiry: time::Duration) -> Result<Self, DKIMError> {
+        let now = self
+            .tags
+            .iter()
+            .find(|(key, _)| key == "t")
+            .and_then(|(_, value)| value.parse::<i64>().ok())
+            .ok_or(DKIMError::BuilderError("time must be set before expiry"))?;
+        Ok(self.add_tag("x", &(now + expiry.whole_seconds()).to_string()))
+    }
+
+    pub(crate) fn build(self) -> Result<DKIMHeader, DKIMError> {
+        let raw_bytes = self
+            .tags
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+            + ";";
+        Ok(DKIMHeader {
+            tags: self.tags,
+            raw_bytes,
+        })
+    }
+}
@@ -0,0 +1,87 @@
+//! Generation of DKIM keypairs and the DNS TXT records that publish them.
+
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+use crate::{DKIMError, DkimPrivateKey};
+
+/// Generate a new RSA keypair of `bits` size, ready to hand to
+/// [`crate::SignerBuilder::with_private_key`]. 2048 bits is the commonly
+/// recommended minimum for DKIM signing keys.
+pub fn generate_rsa(bits: usize) -> Result<DkimPrivateKey, DKIMError> {
+    let private_key = RsaPrivateKey::new(&mut rand::rngs::OsRng, bits)
+        .map_err(|err| DKIMError::KeyGenerationError(err.to_string()))?;
+    Ok(DkimPrivateKey::Rsa(private_key))
+}
+
+/// Generate a new Ed25519 keypair, ready to hand to
+/// [`crate::SignerBuilder::with_private_key`].
+pub fn generate_ed25519() -> DkimPrivateKey {
+    let keypair = ed25519_dalek::Keypair::generate(&mut rand::rngs::OsRng);
+    DkimPrivateKey::Ed25519(keypair)
+}
+
+/// Format the `v=DKIM1; k=...; p=...` value to publish as the TXT record at
+/// `<selector>._domainkey.<domain>` for this key.
+pub fn dns_txt_record(key: &DkimPrivateKey) -> Result<String, DKIMError> {
+    match key {
+        DkimPrivateKey::Rsa(private_key) => {
+            let public_key = RsaPublicKey::from(private_key);
+            let der = public_key
+                .to_public_key_der()
+                .map_err(|err| DKIMError::KeyGenerationError(err.to_string()))?;
+            Ok(format!(
+                "v=DKIM1; k=rsa; p={}",
+                base64::encode(der.as_bytes())
+            ))
+        }
+        DkimPrivateKey::Ed25519(keypair) => Ok(format!(
+            "v=DKIM1; k=ed25519; p={}",
+            base64::encode(keypair.public.as_bytes())
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::PublicKeyParts;
+
+    #[test]
+    fn test_generate_rsa_produces_a_usable_key_of_the_requested_size() {
+        let key = generate_rsa(2048).unwrap();
+        match key {
+            DkimPrivateKey::Rsa(private_key) => assert_eq!(private_key.size() * 8, 2048),
+            DkimPrivateKey::Ed25519(_) => panic!("expected an RSA key"),
+        }
+    }
+
+    #[test]
+    fn test_generate_ed25519_produces_a_keypair() {
+        let key = generate_ed25519();
+        assert!(matches!(key, DkimPrivateKey::Ed25519(_)));
+    }
+
+    #[test]
+    fn test_dns_txt_record_rsa() {
+        let key = generate_rsa(2048).unwrap();
+        let record = dns_txt_record(&key).unwrap();
+        assert!(record.starts_with("v=DKIM1; k=rsa; p="));
+    }
+
+    #[test]
+    fn test_dns_txt_record_ed25519() {
+        let key = generate_ed25519();
+        let record = dns_txt_record(&key).unwrap();
+
+        let keypair = match &key {
+            DkimPrivateKey::Ed25519(keypair) => keypair,
+            DkimPrivateKey::Rsa(_) => panic!("expected an Ed25519 key"),
+        };
+        let expected = format!(
+            "v=DKIM1; k=ed25519; p={}",
+            base64::encode(keypair.public.as_bytes())
+        );
+        assert_eq!(record, expected);
+    }
+}
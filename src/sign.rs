@@ -17,6 +17,9 @@ pub struct SignerBuilder<'a> {
     header_canonicalization: canonicalization::Type,
     body_canonicalization: canonicalization::Type,
     expiry: Option<time::Duration>,
+    body_length: Option<usize>,
+    identity: Option<&'a str>,
+    oversigned_headers: Option<&'a [&'a str]>,
 }
 
 impl<'a> SignerBuilder<'a> {
@@ -31,6 +34,9 @@ impl<'a> SignerBuilder<'a> {
             time: None,
             header_canonicalization: canonicalization::Type::Simple,
             body_canonicalization: canonicalization::Type::Simple,
+            body_length: None,
+            identity: None,
+            oversigned_headers: None,
         }
     }
 
@@ -88,6 +94,44 @@ impl<'a> SignerBuilder<'a> {
         self
     }
 
+    /// Only sign the first `value` octets of the canonicalized body and emit
+    /// the `l=` tag recording that length.
+    ///
+    /// This is unsafe: a signature carrying `l=` stays valid if an attacker
+    /// appends arbitrary content to the message after the signed prefix (the
+    /// "DKIM `l=` exploit"). Only enable this for a sender you control where
+    /// the risk is understood, and expect verifiers to reject or ignore such
+    /// signatures unless they have explicitly opted into relaxed mode.
+    pub fn with_body_length(mut self, value: usize) -> Self {
+        self.body_length = Some(value);
+        self
+    }
+
+    /// Emit the `i=` Agent-or-User-Identifier tag. `value`'s domain part
+    /// (after the last `@`) must be `signing_domain` or a subdomain of it;
+    /// this is checked when the builder is built, once `signing_domain` is
+    /// known.
+    pub fn with_identity(mut self, value: &'a str) -> Self {
+        self.identity = Some(value);
+        self
+    }
+
+    /// Append each given header name once more to the end of the `h=` tag,
+    /// even if the message only carries one instance of it. A name also
+    /// passed to [`Self::with_signed_headers`] thus appears twice in `h=`;
+    /// any other name appears once.
+    ///
+    /// Without oversigning, an attacker who controls only the envelope and
+    /// not the signing key can add a second `Subject`, `From`, or `To`
+    /// header after the message is signed; MUAs that read the topmost
+    /// instance would then display attacker-controlled content while the
+    /// signature still verifies. Reserving the extra slot in `h=` makes such
+    /// an injected header break the signature instead.
+    pub fn with_oversigned_headers(mut self, headers: &'a [&'a str]) -> Self {
+        self.oversigned_headers = Some(headers);
+        self
+    }
+
     /// Build an instance of the Signer
     /// Must be provided: signed_headers, private_key, selector, logger and
     /// signing_domain.
@@ -102,28 +146,54 @@ impl<'a> SignerBuilder<'a> {
             DkimPrivateKey::Ed25519(_) => hash::HashAlgo::Ed25519Sha256,
         };
 
+        let signing_domain = self
+            .signing_domain
+            .ok_or(BuilderError("missing required signing domain"))?;
+
+        if let Some(identity) = self.identity {
+            let identity_domain = identity
+                .rsplit_once('@')
+                .map(|(_, domain)| domain)
+                .ok_or(BuilderError("identity must contain an '@'"))?;
+            let matches_domain = identity_domain.eq_ignore_ascii_case(signing_domain)
+                || identity_domain
+                    .to_lowercase()
+                    .ends_with(&format!(".{}", signing_domain.to_lowercase()));
+            if !matches_domain {
+                return Err(DKIMError::IdentityDomainMismatch(identity.to_string()));
+            }
+        }
+
+        let mut signed_headers: Vec<String> = self
+            .signed_headers
+            .ok_or(BuilderError("missing required signed headers"))?
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        if let Some(oversigned_headers) = self.oversigned_headers {
+            signed_headers.extend(oversigned_headers.iter().map(|h| h.to_string()));
+        }
+
         Ok(Signer {
-            signed_headers: self
-                .signed_headers
-                .ok_or(BuilderError("missing required signed headers"))?,
+            signed_headers,
             private_key,
             selector: self
                 .selector
                 .ok_or(BuilderError("missing required selector"))?,
-            signing_domain: self
-                .signing_domain
-                .ok_or(BuilderError("missing required logger"))?,
+            signing_domain,
             header_canonicalization: self.header_canonicalization,
             body_canonicalization: self.body_canonicalization,
             expiry: self.expiry,
             hash_algo,
             time: self.time,
+            body_length: self.body_length,
+            identity: self.identity,
         })
     }
 }
 
 pub struct Signer<'a> {
-    signed_headers: &'a [&'a str],
+    signed_headers: Vec<String>,
     private_key: DkimPrivateKey,
     selector: &'a str,
     signing_domain: &'a str,
@@ -132,6 +202,8 @@ pub struct Signer<'a> {
     expiry: Option<time::Duration>,
     hash_algo: hash::HashAlgo,
     time: Option<time::OffsetDateTime>,
+    body_length: Option<usize>,
+    identity: Option<&'a str>,
 }
 
 /// DKIM signer. Use the [SignerBuilder] to build an instance.
@@ -186,7 +258,11 @@ impl<'a> Signer<'a> {
             .add_tag("v", "1")
             .add_tag("a", hash_algo)
             .add_tag("d", self.signing_domain)
-            .add_tag("s", self.selector)
+            .add_tag("s", self.selector);
+        if let Some(identity) = self.identity {
+            builder = builder.add_tag("i", identity);
+        }
+        builder = builder
             .add_tag(
                 "c",
                 &format!(
@@ -196,7 +272,10 @@ impl<'a> Signer<'a> {
                 ),
             )
             .add_tag("bh", body_hash)
-            .set_signed_headers(self.signed_headers);
+            .set_signed_headers(&self.signed_headers);
+        if let Some(body_length) = self.body_length {
+            builder = builder.add_tag("l", &body_length.to_string());
+        }
         if let Some(expiry) = self.expiry {
             builder = builder.set_expiry(expiry)?;
         }
@@ -213,7 +292,7 @@ impl<'a> Signer<'a> {
         &self,
         email: &'b mailparse::ParsedMail<'b>,
     ) -> Result<String, DKIMError> {
-        let length = None;
+        let length = self.body_length;
         let canonicalization = self.body_canonicalization.clone();
         hash::compute_body_hash(canonicalization, length, self.hash_algo.clone(), email)
     }
@@ -331,4 +410,103 @@ Joe."#
 
         assert_eq!(header, "DKIM-Signature: v=1; a=ed25519-sha256; d=football.example.com; s=brisbane; c=relaxed/relaxed; bh=2jUSOH9NhtVGCQWNr9BrIAPreKQjO6Sn7XIkfJVOzv8=; h=from:to:subject:date:message-id:from:subject:date; t=1528637909; b=wITr2H3sBuBfMsnUwlRTO7Oq/C/jd2vubDm50DrXtMFEBLRiz9GfrgCozcg764+gYqWXV3Snd1ynYh8sJ5BXBg==;")
     }
+
+    #[test]
+    fn test_sign_with_body_length_truncates_and_emits_l_tag() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let time = OffsetDateTime::parse("2021-01-01T00:00:01.444Z", &Rfc3339).unwrap();
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_time(time)
+            .with_body_length(11)
+            .build()
+            .unwrap();
+        let header = signer.sign(&email).unwrap();
+
+        assert!(
+            header.contains("l=11;"),
+            "expected an l=11 tag in {}",
+            header
+        );
+
+        let expected_bh = hash::compute_body_hash(
+            canonicalization::Type::Simple,
+            Some(11),
+            hash::HashAlgo::RsaSha256,
+            &email,
+        )
+        .unwrap();
+        assert!(
+            header.contains(&format!("bh={};", expected_bh)),
+            "expected bh={} in {}",
+            expected_bh,
+            header
+        );
+    }
+
+    #[test]
+    fn test_with_identity_requires_matching_or_subdomain() {
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+
+        let err = SignerBuilder::new()
+            .with_signed_headers(&["From"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key.clone()))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_identity("user@evil.com")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, DKIMError::IdentityDomainMismatch(_)));
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_identity("user@mail.example.com")
+            .build()
+            .unwrap();
+        let email = mailparse::parse_mail(b"From: a@example.com\r\n\r\nhi\r\n").unwrap();
+        let header = signer.sign(&email).unwrap();
+        assert!(header.contains("i=user@mail.example.com;"));
+    }
+
+    #[test]
+    fn test_with_oversigned_headers_appends_second_occurrence() {
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let email = mailparse::parse_mail(b"From: a@example.com\r\n\r\nhi\r\n").unwrap();
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From"])
+            .unwrap()
+            .with_private_key(DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_oversigned_headers(&["From", "Subject"])
+            .build()
+            .unwrap();
+        let header = signer.sign(&email).unwrap();
+
+        assert!(header.contains("h=from:from:subject;"));
+    }
 }
@@ -0,0 +1,664 @@
+//! DKIM verification, see <https://datatracker.ietf.org/doc/html/rfc6376#section-6>.
+
+use std::collections::HashMap;
+
+use ed25519_dalek::Verifier as _;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{PaddingScheme, PublicKey as _};
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::canonicalization;
+use crate::hash;
+use crate::header::DKIMHeader;
+use crate::DKIMError;
+
+/// Outcome of verifying a single `DKIM-Signature` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureResult {
+    Pass,
+    Fail(String),
+    Neutral(String),
+}
+
+/// Resolves DKIM public key TXT records. Implement this against whichever
+/// DNS client the caller already uses; tests can supply a [`StaticResolver`]
+/// instead of hitting the network.
+pub trait DnsResolver {
+    fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DKIMError>;
+}
+
+/// A [`DnsResolver`] backed by a fixed in-memory map of name to TXT record
+/// strings, for tests.
+#[derive(Debug, Default, Clone)]
+pub struct StaticResolver {
+    records: HashMap<String, Vec<String>>,
+}
+
+impl StaticResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_record(mut self, name: &str, record: &str) -> Self {
+        self.records
+            .entry(name.to_string())
+            .or_default()
+            .push(record.to_string());
+        self
+    }
+}
+
+impl DnsResolver for StaticResolver {
+    fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DKIMError> {
+        self.records.get(name).cloned().ok_or(DKIMError::KeyMissing)
+    }
+}
+
+/// Builder for [`Verifier`].
+#[derive(Default)]
+pub struct VerifierBuilder<'a> {
+    resolver: Option<Box<dyn DnsResolver + 'a>>,
+    allow_body_length_tag: bool,
+}
+
+impl<'a> VerifierBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supply the DNS resolver used to fetch `<selector>._domainkey.<domain>`
+    /// TXT records.
+    pub fn with_dns_resolver(mut self, resolver: impl DnsResolver + 'a) -> Self {
+        self.resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Relax the default strict mode and accept signatures carrying an `l=`
+    /// body length tag.
+    ///
+    /// By default the verifier refuses any signature with `l=` because it
+    /// lets an attacker append arbitrary content after the signed prefix
+    /// (the "DKIM `l=` exploit") while the signature still reports pass.
+    /// Only enable this if you understand and accept that risk for your
+    /// deployment.
+    pub fn with_relaxed_body_length(mut self, value: bool) -> Self {
+        self.allow_body_length_tag = value;
+        self
+    }
+
+    pub fn build(self) -> Result<Verifier<'a>, DKIMError> {
+        Ok(Verifier {
+            resolver: self
+                .resolver
+                .ok_or(DKIMError::BuilderError("missing required dns resolver"))?,
+            allow_body_length_tag: self.allow_body_length_tag,
+        })
+    }
+}
+
+enum PublicKey {
+    Rsa(rsa::RsaPublicKey),
+    Ed25519(ed25519_dalek::PublicKey),
+}
+
+/// A public key DNS record together with the optional flags from RFC 6376
+/// §3.6.1 that constrain how it may be used.
+struct PublicKeyRecord {
+    key: PublicKey,
+    /// `h=` restricts which `a=` hash functions the key may be used with;
+    /// `None` means the record doesn't restrict it.
+    allowed_hashes: Option<Vec<String>>,
+    /// `t=y` marks the domain as testing DKIM (RFC 6376 §3.6.1): a
+    /// signature whose cryptographic check fails is reported as
+    /// [`SignatureResult::Neutral`] rather than [`SignatureResult::Fail`];
+    /// a signature that verifies is still [`SignatureResult::Pass`].
+    testing: bool,
+}
+
+/// Outcome of [`Verifier::verify_one_inner`]'s cryptographic check, before
+/// it is mapped into a [`SignatureResult`].
+enum VerifyOutcome {
+    Pass,
+    /// The signature did not verify, but the signing domain's key record is
+    /// marked `t=y` (testing), so the failure carries no penalty.
+    TestingFailure(DKIMError),
+}
+
+struct ParsedSignature {
+    domain: String,
+    selector: String,
+    hash_algo: hash::HashAlgo,
+    header_canon: canonicalization::Type,
+    body_canon: canonicalization::Type,
+    signed_headers: String,
+    body_hash: String,
+    body_length: Option<usize>,
+    signature: Vec<u8>,
+    header_for_hash: DKIMHeader,
+}
+
+/// DKIM verifier. Use [`VerifierBuilder`] to build an instance.
+pub struct Verifier<'a> {
+    resolver: Box<dyn DnsResolver + 'a>,
+    allow_body_length_tag: bool,
+}
+
+impl<'a> Verifier<'a> {
+    /// Verify every `DKIM-Signature` header present on `email`, returning one
+    /// result per signature in the order the headers appear.
+    pub fn verify<'b>(
+        &self,
+        email: &'b mailparse::ParsedMail<'b>,
+    ) -> Result<Vec<SignatureResult>, DKIMError> {
+        Ok(email
+            .headers
+            .iter()
+            .filter(|h| h.get_key_ref().eq_ignore_ascii_case(crate::HEADER))
+            .map(|h| self.verify_one(email, &h.get_value()))
+            .collect())
+    }
+
+    fn verify_one<'b>(&self, email: &'b mailparse::ParsedMail<'b>, raw: &str) -> SignatureResult {
+        match self.verify_one_inner(email, raw) {
+            Ok(VerifyOutcome::Pass) => SignatureResult::Pass,
+            Ok(VerifyOutcome::TestingFailure(err)) => SignatureResult::Neutral(err.to_string()),
+            Err(err) => SignatureResult::Fail(err.to_string()),
+        }
+    }
+
+    fn verify_one_inner<'b>(
+        &self,
+        email: &'b mailparse::ParsedMail<'b>,
+        raw: &str,
+    ) -> Result<VerifyOutcome, DKIMError> {
+        let parsed = Self::parse_signature(raw)?;
+
+        if parsed.body_length.is_some() && !self.allow_body_length_tag {
+            return Err(DKIMError::UnsafeBodyLengthTag);
+        }
+
+        let computed_body_hash = hash::compute_body_hash(
+            parsed.body_canon,
+            parsed.body_length,
+            parsed.hash_algo,
+            email,
+        )?;
+        if computed_body_hash != parsed.body_hash {
+            return Err(DKIMError::BodyHashMismatch);
+        }
+
+        let header_hash = hash::compute_headers_hash(
+            parsed.header_canon,
+            &parsed.signed_headers,
+            parsed.hash_algo,
+            &parsed.header_for_hash,
+            email,
+        )?;
+
+        let record_name = format!("{}._domainkey.{}", parsed.selector, parsed.domain);
+        let txt_records = self.resolver.lookup_txt(&record_name)?;
+        let key_record = Self::parse_public_key_record(&txt_records)?;
+
+        if let Some(allowed_hashes) = &key_record.allowed_hashes {
+            let requested = parsed.hash_algo.dns_hash_name();
+            if !allowed_hashes.iter().any(|h| h == requested) {
+                return Err(DKIMError::UnsupportedHashAlgorithm(requested.to_string()));
+            }
+        }
+
+        match Self::verify_signature(
+            &key_record.key,
+            parsed.hash_algo,
+            &header_hash,
+            &parsed.signature,
+        ) {
+            Ok(()) => Ok(VerifyOutcome::Pass),
+            // RFC 6376 §3.6.1: a testing domain's signature must not be
+            // penalized for failing to verify. A domain's signature that
+            // *does* verify is reported as a normal Pass either way.
+            Err(err) if key_record.testing => Ok(VerifyOutcome::TestingFailure(err)),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn parse_signature(raw: &str) -> Result<ParsedSignature, DKIMError> {
+        let header = DKIMHeader::parse(raw)?;
+
+        if header.get_tag("v").unwrap_or("1") != "1" {
+            return Err(DKIMError::UnsupportedVersion(
+                header.get_required_tag("v"),
+            ));
+        }
+
+        let require = |tag: &str| {
+            header
+                .get_tag(tag)
+                .map(str::to_string)
+                .ok_or_else(|| DKIMError::SignatureMissingTag(tag.to_string()))
+        };
+
+        let domain = require("d")?;
+        let selector = require("s")?;
+        let signed_headers = require("h")?;
+        let body_hash = require("bh")?;
+        let signature_b64 = require("b")?;
+
+        let hash_algo = match require("a")?.as_str() {
+            "rsa-sha1" => hash::HashAlgo::RsaSha1,
+            "rsa-sha256" => hash::HashAlgo::RsaSha256,
+            "ed25519-sha256" => hash::HashAlgo::Ed25519Sha256,
+            other => return Err(DKIMError::UnsupportedHashAlgorithm(other.to_string())),
+        };
+
+        let (header_canon, body_canon) = match header.get_tag("c") {
+            Some(c) => canonicalization::parse_pair(c)?,
+            None => (canonicalization::Type::Simple, canonicalization::Type::Simple),
+        };
+
+        let signature = base64::decode(signature_b64.replace(['\r', '\n', ' '], ""))
+            .map_err(|err| DKIMError::SignatureSyntaxError(err.to_string()))?;
+
+        let body_length = header
+            .get_tag("l")
+            .map(|value| {
+                value
+                    .parse::<usize>()
+                    .map_err(|err| DKIMError::SignatureSyntaxError(err.to_string()))
+            })
+            .transpose()?;
+
+        // The header hash is computed with b= emptied out, exactly as the
+        // signer did when producing this signature. RFC 6376 requires this
+        // to start from the exact received bytes, so splice the b= value
+        // out of `raw` in place rather than reformatting from `header.tags`
+        // (real-world signers fold/space the tag-list differently than any
+        // single canonical rendering would).
+        let header_for_hash = DKIMHeader {
+            tags: header.tags.clone(),
+            raw_bytes: Self::blank_b_tag(raw)?,
+        };
+
+        Ok(ParsedSignature {
+            domain,
+            selector,
+            hash_algo,
+            header_canon,
+            body_canon,
+            signed_headers,
+            body_hash,
+            body_length,
+            signature,
+            header_for_hash,
+        })
+    }
+
+    /// Blank the `b=` tag's value in `raw` in place, preserving every other
+    /// byte (including whitespace and tag ordering) exactly as received.
+    fn blank_b_tag(raw: &str) -> Result<String, DKIMError> {
+        let mut found = false;
+        let mut out = String::with_capacity(raw.len());
+        for (i, part) in raw.split(';').enumerate() {
+            if i > 0 {
+                out.push(';');
+            }
+            let is_b_tag = part
+                .trim()
+                .split_once('=')
+                .map(|(tag, _)| tag.trim() == "b")
+                .unwrap_or(false);
+            if is_b_tag {
+                found = true;
+                let eq_pos = part.find('=').expect("is_b_tag implies an '=' is present");
+                out.push_str(&part[..=eq_pos]);
+            } else {
+                out.push_str(part);
+            }
+        }
+        if !found {
+            return Err(DKIMError::SignatureMissingTag("b".to_string()));
+        }
+        Ok(out)
+    }
+
+    fn parse_public_key_record(records: &[String]) -> Result<PublicKeyRecord, DKIMError> {
+        let record = records.concat();
+
+        let mut key_type = "rsa";
+        let mut public_key_b64 = None;
+        let mut allowed_hashes = None;
+        let mut testing = false;
+        for part in record.split(';') {
+            let Some((tag, value)) = part.trim().split_once('=') else {
+                continue;
+            };
+            match tag.trim() {
+                "v" if value.trim() != "DKIM1" => {
+                    return Err(DKIMError::UnsupportedVersion(value.trim().to_string()))
+                }
+                "k" => key_type = value.trim(),
+                "p" => public_key_b64 = Some(value.trim().to_string()),
+                "h" => {
+                    allowed_hashes = Some(
+                        value
+                            .trim()
+                            .split(':')
+                            .map(|h| h.trim().to_string())
+                            .collect::<Vec<_>>(),
+                    )
+                }
+                "t" => testing = value.trim().split(':').any(|flag| flag == "y"),
+                _ => {}
+            }
+        }
+
+        let public_key_b64 = public_key_b64.ok_or(DKIMError::KeyMissing)?;
+        let der = base64::decode(public_key_b64.replace([' ', '\r', '\n'], ""))
+            .map_err(|err| DKIMError::KeyMalformed(err.to_string()))?;
+
+        let key = match key_type {
+            "rsa" => rsa::RsaPublicKey::from_public_key_der(&der)
+                .map(PublicKey::Rsa)
+                .map_err(|err| DKIMError::KeyMalformed(err.to_string())),
+            "ed25519" => ed25519_dalek::PublicKey::from_bytes(&der)
+                .map(PublicKey::Ed25519)
+                .map_err(|err| DKIMError::KeyMalformed(err.to_string())),
+            other => Err(DKIMError::UnsupportedKeyType(other.to_string())),
+        }?;
+
+        Ok(PublicKeyRecord {
+            key,
+            allowed_hashes,
+            testing,
+        })
+    }
+
+    fn verify_signature(
+        key: &PublicKey,
+        hash_algo: hash::HashAlgo,
+        header_hash: &[u8],
+        signature: &[u8],
+    ) -> Result<(), DKIMError> {
+        match key {
+            PublicKey::Rsa(public_key) => {
+                let padding = match hash_algo {
+                    hash::HashAlgo::RsaSha1 => PaddingScheme::new_pkcs1v15_sign::<Sha1>(),
+                    hash::HashAlgo::RsaSha256 => PaddingScheme::new_pkcs1v15_sign::<Sha256>(),
+                    hash::HashAlgo::Ed25519Sha256 => {
+                        return Err(DKIMError::UnsupportedKeyType(
+                            "ed25519 signature with rsa public key".to_string(),
+                        ))
+                    }
+                };
+                public_key
+                    .verify(padding, header_hash, signature)
+                    .map_err(|_| DKIMError::SignatureDidNotVerify)
+            }
+            PublicKey::Ed25519(public_key) => {
+                let signature = ed25519_dalek::Signature::from_bytes(signature)
+                    .map_err(|_| DKIMError::SignatureDidNotVerify)?;
+                public_key
+                    .verify(header_hash, &signature)
+                    .map_err(|_| DKIMError::SignatureDidNotVerify)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keygen, SignerBuilder};
+    use rsa::pkcs1::DecodeRsaPrivateKey;
+    use std::path::Path;
+
+    #[test]
+    fn test_verify_roundtrip_rsa() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let dns_record =
+            keygen::dns_txt_record(&crate::DkimPrivateKey::Rsa(private_key.clone())).unwrap();
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(crate::DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+        let dkim_header = signer.sign(&email).unwrap();
+        let raw_email = format!("{}\r\n{}", dkim_header, "Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\nHello Alice\n        ");
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record("s20._domainkey.example.com", &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&signed_email).unwrap();
+        assert_eq!(results, vec![SignatureResult::Pass]);
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_key() {
+        let email = mailparse::parse_mail(
+            "DKIM-Signature: v=1; a=rsa-sha256; d=example.com; s=s20; c=simple/simple; bh=Ba3gj8+xBPQLJTahTfzW6RbWQ/XPgESxkCi2B66PSQg=; h=from:subject; b=aaaa;\r\nSubject: subject\r\nFrom: a@example.com\r\n\r\nHello\r\n"
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(StaticResolver::new())
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&email).unwrap();
+        assert_eq!(
+            results[0],
+            SignatureResult::Fail(DKIMError::KeyMissing.to_string())
+        );
+    }
+
+    fn sign_with_body_length() -> (String, String, String) {
+        let body = "Hello Alice\r\n        ";
+        let email = mailparse::parse_mail(
+            format!("Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\n{}", body)
+                .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let dns_record =
+            keygen::dns_txt_record(&crate::DkimPrivateKey::Rsa(private_key.clone())).unwrap();
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(crate::DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .with_body_length(5)
+            .build()
+            .unwrap();
+        let dkim_header = signer.sign(&email).unwrap();
+        let raw_email = format!(
+            "{}\r\nSubject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\n{}",
+            dkim_header, body
+        );
+        (raw_email, dns_record, "s20._domainkey.example.com".to_string())
+    }
+
+    #[test]
+    fn test_verify_rejects_l_tag_by_default() {
+        let (raw_email, dns_record, record_name) = sign_with_body_length();
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record(&record_name, &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&signed_email).unwrap();
+        match &results[0] {
+            SignatureResult::Fail(reason) => {
+                assert!(reason.contains("l="), "unexpected failure reason: {}", reason)
+            }
+            other => panic!("expected Fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_accepts_l_tag_when_relaxed() {
+        let (raw_email, dns_record, record_name) = sign_with_body_length();
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record(&record_name, &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .with_relaxed_body_length(true)
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&signed_email).unwrap();
+        assert_eq!(results, vec![SignatureResult::Pass]);
+    }
+
+    #[test]
+    fn test_verify_rejects_hash_algo_excluded_by_h_flag() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let dns_record = format!(
+            "{}; h=sha1",
+            keygen::dns_txt_record(&crate::DkimPrivateKey::Rsa(private_key.clone())).unwrap()
+        );
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(crate::DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+        let dkim_header = signer.sign(&email).unwrap();
+        let raw_email = format!("{}\r\n{}", dkim_header, "Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\nHello Alice\n        ");
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record("s20._domainkey.example.com", &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&signed_email).unwrap();
+        assert!(matches!(results[0], SignatureResult::Fail(_)));
+    }
+
+    #[test]
+    fn test_verify_passes_for_testing_domain_with_valid_signature() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        let dns_record = format!(
+            "{}; t=y",
+            keygen::dns_txt_record(&crate::DkimPrivateKey::Rsa(private_key.clone())).unwrap()
+        );
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(crate::DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+        let dkim_header = signer.sign(&email).unwrap();
+        let raw_email = format!("{}\r\n{}", dkim_header, "Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\nHello Alice\n        ");
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record("s20._domainkey.example.com", &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .build()
+            .unwrap();
+
+        // A genuinely valid signature must stay Pass even for a testing
+        // domain: t=y only excuses failures, RFC 6376 §3.6.1.
+        let results = verifier.verify(&signed_email).unwrap();
+        assert_eq!(results, vec![SignatureResult::Pass]);
+    }
+
+    #[test]
+    fn test_verify_reports_neutral_for_failing_signature_in_testing_domain() {
+        let email = mailparse::parse_mail(
+            r#"Subject: subject
+From: Sven Sauleau <sven@cloudflare.com>
+
+Hello Alice
+        "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let private_key =
+            rsa::RsaPrivateKey::read_pkcs1_pem_file(Path::new("./test/keys/2022.private")).unwrap();
+        // A key that does not match the one used to sign, so the
+        // cryptographic check fails and the domain's t=y leniency applies.
+        let other_key = keygen::generate_rsa(2048).unwrap();
+        let dns_record = format!("{}; t=y", keygen::dns_txt_record(&other_key).unwrap());
+
+        let signer = SignerBuilder::new()
+            .with_signed_headers(&["From", "Subject"])
+            .unwrap()
+            .with_private_key(crate::DkimPrivateKey::Rsa(private_key))
+            .with_selector("s20")
+            .with_signing_domain("example.com")
+            .build()
+            .unwrap();
+        let dkim_header = signer.sign(&email).unwrap();
+        let raw_email = format!("{}\r\n{}", dkim_header, "Subject: subject\r\nFrom: Sven Sauleau <sven@cloudflare.com>\r\n\r\nHello Alice\n        ");
+        let signed_email = mailparse::parse_mail(raw_email.as_bytes()).unwrap();
+
+        let resolver = StaticResolver::new().with_record("s20._domainkey.example.com", &dns_record);
+        let verifier = VerifierBuilder::new()
+            .with_dns_resolver(resolver)
+            .build()
+            .unwrap();
+
+        let results = verifier.verify(&signed_email).unwrap();
+        assert!(matches!(results[0], SignatureResult::Neutral(_)));
+    }
+}